@@ -2,6 +2,8 @@ use lazy_static::lazy_static;
 use volatile::Volatile;
 use core::fmt;
 use spin::Mutex;
+use x86_64::instructions::interrupts;
+use x86_64::instructions::port::Port;
 
 
 #[allow(dead_code)]
@@ -30,13 +32,53 @@ pub enum Color {
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(transparent)]
-
-struct ColorCode(u8);
+pub struct ColorCode(u8);
 //The color code is 8 bits, bbbbffff
 //So we start 0000bbbb -> bbbb0000 -> bbbbffff
+//
+//Bit 7 (the top bit of the background nibble) doubles as the blink bit on
+//real VGA hardware: when blink is enabled, background is only 3 bits wide
+//(0x0-0x7) and bit 7 instead makes the character blink. Backgrounds 0x8-0xF
+//("light" backgrounds) are only reachable with blink disabled, which some
+//BIOSes/terminals require toggling explicitly. `new` always builds with
+//blink off so the full background range behaves as most callers expect;
+//use `with_blink` when you need blink or want to be explicit about it.
 impl ColorCode {
     fn new(foreground: Color, background: Color) -> ColorCode {
-        ColorCode((background as u8) << 4 | (foreground as u8))
+        ColorCode::with_blink(foreground, background, false)
+    }
+
+    /// Builds an attribute byte, explicitly choosing whether the high bit
+    /// is the blink flag or the top bit of the background color.
+    ///
+    /// When `blink` is `true`, `background` is masked to 0x0-0x7 since bit 7
+    /// is repurposed as the blink flag instead of background bit 3 -
+    /// backgrounds 0x8-0xF ([`Color::DarkGray`] and up) are only reachable
+    /// with `blink` set to `false`.
+    pub fn with_blink(foreground: Color, background: Color, blink: bool) -> ColorCode {
+        if blink {
+            ColorCode(0x80 | (background as u8 & 0x7) << 4 | (foreground as u8))
+        } else {
+            ColorCode((background as u8) << 4 | (foreground as u8))
+        }
+    }
+
+    /// Sets bit 7 of an already-built attribute byte as the blink flag.
+    /// When enabling blink, the background is masked down to 0x0-0x7 first
+    /// so it can't collide with the blink flag; when disabling blink, the
+    /// byte is returned unchanged: the full 4-bit background nibble is left
+    /// untouched, so a bit 7 that was already set stays set and is read as
+    /// background bit 3.
+    fn set_blink_bit(self, blink: bool) -> ColorCode {
+        let ColorCode(raw) = self;
+        let foreground = raw & 0x0f;
+        if blink {
+            let background = (raw >> 4) & 0x7;
+            ColorCode(0x80 | background << 4 | foreground)
+        } else {
+            let background = (raw >> 4) & 0xf;
+            ColorCode(background << 4 | foreground)
+        }
     }
 }
 
@@ -52,6 +94,10 @@ struct ScreenChar {
 const BUFFER_HEIGHT: usize = 25;
 const BUFFER_WIDTH: usize = 80;
 
+//CRTC index/data port pair used to drive the hardware text cursor
+const CURSOR_COMMAND_PORT: u16 = 0x3D4;
+const CURSOR_DATA_PORT: u16 = 0x3D5;
+
 #[repr(transparent)]
 struct Buffer {
     chars: [[Volatile<ScreenChar>; BUFFER_WIDTH]; BUFFER_HEIGHT],
@@ -73,26 +119,45 @@ impl Writer {
                     }
                 
 
-                let row = BUFFER_HEIGHT -1; 
+                let row = BUFFER_HEIGHT -1;
                 let col = self.column_position;
-                
+
                 let color_code = self.color_code;
                 self.buffer.chars[row][col].write(ScreenChar {
                     ascii_character: byte,
                     color_code,
                 });
                 self.column_position += 1;
+                self.update_cursor(row, self.column_position);
             }
         }
     }
 
+    /// Turns on the blink flag on the writer's current color, correctly
+    /// masking the high bit so it doesn't collide with the background
+    /// color (see [`ColorCode::with_blink`]). `set_blink(false)` leaves the
+    /// current color unchanged; it does not clear a bit 7 that is already set.
+    pub fn set_blink(&mut self, blink: bool) {
+        self.color_code = self.color_code.set_blink_bit(blink);
+    }
+
+    /// Sets the color future writes will use until changed again.
+    pub fn set_color(&mut self, color_code: ColorCode) {
+        self.color_code = color_code;
+    }
+
+    /// Returns the color currently in effect.
+    pub fn color(&self) -> ColorCode {
+        self.color_code
+    }
+
     pub fn write_string(&mut self, s: &str) {
-        for byte in s.bytes(){
-            match byte {
-                //Printable ASCII byte or newline
-                0x20..=0x7e | b'\n' => self.write_byte(byte),
-               //Out of Range Error
-                _ => self.write_byte(0xfe),
+        for c in s.chars() {
+            match c {
+                //Printable ASCII char or newline
+                ' '..='~' | '\n' => self.write_byte(c as u8),
+                //Anything else goes through the CP437 translation table
+                other => self.write_byte(char_to_cp437(other)),
             }
         }
     }
@@ -112,6 +177,77 @@ impl Writer {
         self.clear_row(BUFFER_HEIGHT -1);
         //Carriage Return
         self.column_position = 0;
+        self.update_cursor(BUFFER_HEIGHT - 1, self.column_position);
+    }
+
+    /// Writes a single byte at an arbitrary cell using the writer's current
+    /// color, without disturbing `column_position` or the hardware cursor.
+    /// Useful for rendering a full-screen TUI where the caller manages
+    /// layout itself rather than going through the scrolling `write_byte`.
+    ///
+    /// Out-of-range `row`/`col` are silently ignored rather than panicking,
+    /// matching the rest of this module's writes, which never touch a cell
+    /// outside `BUFFER_HEIGHT`/`BUFFER_WIDTH` to begin with.
+    pub fn write_char_at(&mut self, row: usize, col: usize, byte: u8) {
+        if row >= BUFFER_HEIGHT || col >= BUFFER_WIDTH {
+            return;
+        }
+
+        let color_code = self.color_code;
+        self.buffer.chars[row][col].write(ScreenChar {
+            ascii_character: byte,
+            color_code,
+        });
+    }
+
+    /// Moves the blinking hardware text cursor to `row`/`col` via the CRTC
+    /// cursor location registers (index 0x0F for the low byte, 0x0E for the
+    /// high byte of `row * BUFFER_WIDTH + col`), independent of where the
+    /// next `write_byte` will land.
+    pub fn set_position(&mut self, row: usize, col: usize) {
+        self.update_cursor(row, col);
+    }
+
+    fn update_cursor(&self, row: usize, col: usize) {
+        let position = row * BUFFER_WIDTH + col;
+
+        let mut command_port: Port<u8> = Port::new(CURSOR_COMMAND_PORT);
+        let mut data_port: Port<u8> = Port::new(CURSOR_DATA_PORT);
+        unsafe {
+            command_port.write(0x0F);
+            data_port.write((position & 0xFF) as u8);
+            command_port.write(0x0E);
+            data_port.write(((position >> 8) & 0xFF) as u8);
+        }
+    }
+
+    /// Shows the hardware cursor, setting its scanline shape via the
+    /// cursor-shape registers (0x0A start, 0x0B end). `cursor_start` and
+    /// `cursor_end` are scanlines within the 8x16 glyph cell (0-15);
+    /// 0-15 draws a full-height block cursor.
+    pub fn enable_cursor(&self, cursor_start: u8, cursor_end: u8) {
+        let mut command_port: Port<u8> = Port::new(CURSOR_COMMAND_PORT);
+        let mut data_port: Port<u8> = Port::new(CURSOR_DATA_PORT);
+        unsafe {
+            command_port.write(0x0A);
+            let current_start = data_port.read();
+            data_port.write((current_start & 0xC0) | cursor_start);
+
+            command_port.write(0x0B);
+            let current_end = data_port.read();
+            data_port.write((current_end & 0xE0) | cursor_end);
+        }
+    }
+
+    /// Hides the hardware cursor by setting the disable bit (bit 5) of the
+    /// cursor-start register.
+    pub fn disable_cursor(&self) {
+        let mut command_port: Port<u8> = Port::new(CURSOR_COMMAND_PORT);
+        let mut data_port: Port<u8> = Port::new(CURSOR_DATA_PORT);
+        unsafe {
+            command_port.write(0x0A);
+            data_port.write(0x20);
+        }
     }
 
     fn clear_row(&mut self, row: usize){
@@ -126,7 +262,75 @@ impl Writer {
     }
 }
 
-
+//The VGA text buffer renders Code Page 437, not Unicode - it's an ASCII
+//superset that reuses 0x80-0xFF for box-drawing, block, and accented
+//glyphs. This maps the chars this codebase is likely to draw (TUI frames,
+//tables, common Latin-1 accents) to their CP437 byte; anything else falls
+//back to 0xfe, the same placeholder glyph used before this translation
+//layer existed.
+fn char_to_cp437(c: char) -> u8 {
+    match c {
+        //Box drawing
+        '─' => 0xc4,
+        '│' => 0xb3,
+        '┌' => 0xda,
+        '┐' => 0xbf,
+        '└' => 0xc0,
+        '┘' => 0xd9,
+        '┤' => 0xb4,
+        '├' => 0xc3,
+        '┬' => 0xc2,
+        '┴' => 0xc1,
+        '┼' => 0xc5,
+        //Shading and block
+        '░' => 0xb0,
+        '▒' => 0xb1,
+        '▓' => 0xb2,
+        '█' => 0xdb,
+        //Math and misc symbols
+        '±' => 0xf1,
+        '÷' => 0xf6,
+        '°' => 0xf8,
+        '²' => 0xfd,
+        '√' => 0xfb,
+        //Accented Latin-1 letters
+        'Ç' => 0x80,
+        'ü' => 0x81,
+        'é' => 0x82,
+        'â' => 0x83,
+        'ä' => 0x84,
+        'à' => 0x85,
+        'å' => 0x86,
+        'ç' => 0x87,
+        'ê' => 0x88,
+        'ë' => 0x89,
+        'è' => 0x8a,
+        'ï' => 0x8b,
+        'î' => 0x8c,
+        'ì' => 0x8d,
+        'Ä' => 0x8e,
+        'Å' => 0x8f,
+        'É' => 0x90,
+        'æ' => 0x91,
+        'Æ' => 0x92,
+        'ô' => 0x93,
+        'ö' => 0x94,
+        'ò' => 0x95,
+        'û' => 0x96,
+        'ù' => 0x97,
+        'ÿ' => 0x98,
+        'Ö' => 0x99,
+        'Ü' => 0x9a,
+        'á' => 0xa0,
+        'í' => 0xa1,
+        'ó' => 0xa2,
+        'ú' => 0xa3,
+        'ñ' => 0xa4,
+        'Ñ' => 0xa5,
+        //Unrepresentable in CP437 - fall back to the existing placeholder
+        _ => 0xfe,
+    }
+}
 
 impl core::fmt::Write for Writer {
 
@@ -159,5 +363,137 @@ macro_rules! println {
 #[doc(hidden)]
 pub fn _print(args: fmt::Arguments) {
     use core::fmt::Write;
-    WRITER.lock().write_fmt(args).unwrap();
+    //Disable interrupts for the whole lock+write so a timer/keyboard
+    //interrupt can't fire mid-print, try to print itself, and deadlock
+    //on a lock we're already holding.
+    interrupts::without_interrupts(|| {
+        WRITER.lock().write_fmt(args).unwrap();
+    });
+}
+
+/// Forcibly releases `WRITER`'s lock without going through the normal
+/// guard drop.
+///
+/// This is an escape hatch for a genuine fault handler only - e.g. the
+/// `#[panic_handler]`, which knows contention on `WRITER` can only mean
+/// the original holder was interrupted mid-print and will never drop its
+/// guard. It must never be called from ordinary code: there, a held lock
+/// can mean a *live* concurrent writer, and forcing it open hands out a
+/// second `&mut Writer` while the first is still in use, then unlocks a
+/// mutex a third party may have since legitimately re-acquired. Call this
+/// immediately before `eprintln!` from the panic handler if printing the
+/// panic message would otherwise deadlock on a wedged `WRITER`; ordinary
+/// `eprint!`/`eprintln!` calls should never need it.
+///
+/// # Safety
+/// Only call this from a context where you can guarantee `WRITER` is not
+/// legitimately held by a still-running writer.
+pub unsafe fn force_unlock() {
+    WRITER.force_unlock();
+}
+
+#[macro_export]
+macro_rules! eprint {
+    ($($arg:tt)*) => ($crate::vga_buffer::_eprint(format_args!($($arg)*)));
+}
+
+#[macro_export]
+macro_rules! eprintln {
+    () => ($crate::eprint!("\n"));
+    ($($arg:tt)*) => ($crate::eprint!("{}\n", format_args!($($arg)*)));
+}
+
+#[doc(hidden)]
+pub fn _eprint(args: fmt::Arguments) {
+    use core::fmt::Write;
+    //Same interrupt-free critical section as `_print`, but swaps in a
+    //red-on-black color for the duration so panic/error output is visible
+    //and unambiguous, then restores whatever color was active before.
+    interrupts::without_interrupts(|| {
+        let mut writer = WRITER.lock();
+        let previous_color = writer.color();
+        writer.set_color(ColorCode::new(Color::Red, Color::Black));
+        writer.write_fmt(args).unwrap();
+        writer.set_color(previous_color);
+    });
+}
+
+#[macro_export]
+macro_rules! print_colored {
+    ($fg:expr, $bg:expr, $($arg:tt)*) => (
+        $crate::vga_buffer::_print_colored($fg, $bg, format_args!($($arg)*))
+    );
+}
+
+#[macro_export]
+macro_rules! println_colored {
+    ($fg:expr, $bg:expr) => ($crate::print_colored!($fg, $bg, "\n"));
+    ($fg:expr, $bg:expr, $($arg:tt)*) => (
+        $crate::print_colored!($fg, $bg, "{}\n", format_args!($($arg)*))
+    );
+}
+
+#[doc(hidden)]
+pub fn _print_colored(foreground: Color, background: Color, args: fmt::Arguments) {
+    use core::fmt::Write;
+    //Save/restore happen under the same lock acquisition as the write so
+    //output from another core/interrupt can't land between the color
+    //change and the restore and come out miscolored.
+    interrupts::without_interrupts(|| {
+        let mut writer = WRITER.lock();
+        let previous_color = writer.color();
+        writer.set_color(ColorCode::new(foreground, background));
+        writer.write_fmt(args).unwrap();
+        writer.set_color(previous_color);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_never_sets_the_blink_bit() {
+        let ColorCode(raw) = ColorCode::new(Color::Green, Color::White);
+        assert_eq!(raw, 0xF2);
+    }
+
+    #[test]
+    fn with_blink_false_keeps_the_full_background_nibble() {
+        let ColorCode(raw) = ColorCode::with_blink(Color::Green, Color::White, false);
+        assert_eq!(raw, 0xF2);
+    }
+
+    #[test]
+    fn with_blink_true_masks_background_to_three_bits() {
+        let ColorCode(raw) = ColorCode::with_blink(Color::Green, Color::White, true);
+        //Background(White = 0xF) is masked to 0x7 and bit 7 becomes the blink flag
+        assert_eq!(raw, 0xF2 & 0x7F | 0x80);
+    }
+
+    #[test]
+    fn set_blink_bit_enabling_masks_background() {
+        let color = ColorCode::with_blink(Color::Green, Color::White, false);
+        let ColorCode(raw) = color.set_blink_bit(true);
+        assert_eq!(raw, 0x80 | (0x7 << 4) | 0x2);
+    }
+
+    #[test]
+    fn set_blink_bit_disabling_preserves_background() {
+        let color = ColorCode::with_blink(Color::Green, Color::White, false);
+        let ColorCode(raw) = color.set_blink_bit(false);
+        assert_eq!(raw, 0xF2);
+    }
+
+    #[test]
+    fn char_to_cp437_maps_box_drawing_and_accents() {
+        assert_eq!(char_to_cp437('─'), 0xc4);
+        assert_eq!(char_to_cp437('█'), 0xdb);
+        assert_eq!(char_to_cp437('é'), 0x82);
+    }
+
+    #[test]
+    fn char_to_cp437_falls_back_for_unrepresentable_chars() {
+        assert_eq!(char_to_cp437('漢'), 0xfe);
+    }
 }